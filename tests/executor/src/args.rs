@@ -15,6 +15,7 @@ use std::{
     fmt::{self, Display, Formatter, Write},
     path::PathBuf,
     str::FromStr,
+    thread,
 };
 
 use anyhow::{Error, Result};
@@ -25,10 +26,22 @@ pub struct Args {
     /// The directory to store intermediate files in
     #[arg(long, default_value_os_t = tests_child("run"))]
     pub run_dir: PathBuf,
+    /// The format to print the results in
+    #[arg(long, value_enum, default_value_t)]
+    pub format: Format,
     #[command(subcommand)]
     pub cmd: Cmd,
 }
 
+#[derive(Clone, Copy, Debug, Default, clap::ValueEnum)]
+pub enum Format {
+    /// A colored table, meant for a person watching the run
+    #[default]
+    Human,
+    /// A single JSON document, meant for CI tooling
+    Json,
+}
+
 #[derive(Debug, Subcommand)]
 pub enum Cmd {
     /// Extract all Typst compilers from the specified archive
@@ -40,6 +53,9 @@ pub enum Cmd {
     GenRefs(#[command(flatten)] CompileArgs),
     /// Test the specified Typst compilers
     Test(#[command(flatten)] CompileArgs),
+    /// Find the first available compiler whose output diverges from a
+    /// known-good baseline document
+    Bisect(#[command(flatten)] BisectArgs),
 }
 
 #[derive(Debug, clap::Args)]
@@ -48,15 +64,88 @@ pub struct CompileArgs {
     /// use all available compilers
     #[arg(default_value_t)]
     pub compilers: CompilersSpec,
-    /// The sample Typst source file
-    #[arg(long, default_value_os_t = tests_child("sample.typ"))]
-    pub sample: PathBuf,
+    /// The directory containing the sample Typst source files (every `.typ`
+    /// file directly inside it is run as its own test)
+    #[arg(long, default_value_os_t = tests_child("samples"))]
+    pub sample_dir: PathBuf,
     /// The directory that contains (or will contain) reference documents
     #[arg(long, default_value_os_t = tests_child("refs"))]
     pub ref_dir: PathBuf,
     /// The project root to pass to Typst
     #[arg(long, default_value_os = ".")]
     pub project_root: PathBuf,
+    /// On a mismatch, overwrite the reference document with the compare
+    /// document instead of failing (only valid with a specific list of
+    /// compilers, not `*`)
+    #[arg(long)]
+    pub bless: bool,
+    /// The number of compilers to run concurrently
+    #[arg(long, default_value_t = default_jobs())]
+    pub jobs: usize,
+    /// How to compare the reference and compare documents
+    #[arg(long, value_enum, default_value_t)]
+    pub compare_mode: CompareMode,
+    /// A JSON file mapping exact compiler names (version ranges aren't
+    /// supported) to the sample names (or `*` for all samples) they're
+    /// documented to mismatch on, so a documented, intentional output change
+    /// doesn't fail CI the way an undocumented one would
+    #[arg(long)]
+    pub known_mismatches: Option<PathBuf>,
+}
+
+#[derive(Debug, clap::Args)]
+pub struct BisectArgs {
+    /// The sample Typst source file to compile
+    pub sample: PathBuf,
+    /// The known-good document to compare against (e.g. a reference document
+    /// generated before a suspected regression)
+    pub baseline: PathBuf,
+    /// The project root to pass to Typst
+    #[arg(long, default_value_os = ".")]
+    pub project_root: PathBuf,
+    /// How to compare the baseline and compiled documents (`rendered` isn't
+    /// supported here, since the baseline is a single document rather than a
+    /// `{p}` page-number template)
+    #[arg(long, value_enum, default_value_t)]
+    pub compare_mode: CompareMode,
+    /// Instead of a binary search, which assumes the output diverges from
+    /// the baseline exactly once across the version range, linearly scan
+    /// every compiler and report every transition
+    #[arg(long)]
+    pub verify: bool,
+}
+
+fn default_jobs() -> usize {
+    thread::available_parallelism().map_or(1, |jobs| jobs.get())
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, clap::ValueEnum)]
+pub enum CompareMode {
+    /// Compare the documents' raw bytes
+    #[default]
+    Digest,
+    /// Compare the documents' bytes after blanking out known
+    /// nondeterministic PDF fields (the trailer `/ID`, `/CreationDate`, and
+    /// `/ModDate`)
+    Normalized,
+    /// Render both documents to per-page PNGs and compare those instead
+    Rendered,
+}
+
+impl CompareMode {
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Self::Digest => "digest",
+            Self::Normalized => "normalized",
+            Self::Rendered => "rendered",
+        }
+    }
+}
+
+impl Display for CompareMode {
+    fn fmt(&self, f: &mut Formatter) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 #[derive(Clone, Debug, Default)]
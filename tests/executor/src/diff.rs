@@ -0,0 +1,148 @@
+// Copyright © 2023 Luke Chambers
+// This file is part of Backtrack.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at <http://www.apache.org/licenses/LICENSE-2.0>.
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Computes and renders a page-by-page visual diff between two sets of
+//! rasterized pages, similar in spirit to `compiletest_rs`'s unified text
+//! diffs but for `--compare-mode rendered`'s PNGs.
+
+use std::{
+    collections::HashMap,
+    io,
+    path::{Path, PathBuf},
+};
+
+use image::{Rgba, RgbaImage};
+use serde::Serialize;
+
+/// The tint applied to a differing pixel in a diff image.
+const DIFF_TINT: Rgba<u8> = Rgba([255, 0, 255, 255]);
+
+/// How much two rasterized pages differ, and where. `page` is the page
+/// number Typst produced it under (as extracted from the `{p}` output
+/// template), not a 0-based position in a list.
+#[derive(Debug, Clone, Serialize)]
+pub struct PageDiff {
+    pub page: u32,
+    pub changed_ratio: f32,
+    pub bbox: Option<(u32, u32, u32, u32)>,
+}
+
+/// Diffs `ref_pages` against `cmp_pages` by page number (as produced by
+/// `rendered_pages_numbered`), writing a side-by-side and a highlighted diff
+/// PNG for each differing page into `out_dir`, named with `file_prefix`
+/// (typically the compiler name) so that diffing the same sample against
+/// multiple compilers into a shared `out_dir` doesn't have one compiler's
+/// diff images overwrite another's. Only the pages both sides produced are
+/// compared here; a difference in page numbers is the caller's concern.
+pub fn diff_pages(
+    ref_pages: &[(u32, PathBuf)],
+    cmp_pages: &[(u32, PathBuf)],
+    out_dir: &Path,
+    file_prefix: &str,
+) -> io::Result<Vec<PageDiff>> {
+    let cmp_by_page: HashMap<u32, &PathBuf> =
+        cmp_pages.iter().map(|(page, path)| (*page, path)).collect();
+    ref_pages
+        .iter()
+        .filter_map(|(page, ref_path)| {
+            cmp_by_page.get(page).map(|cmp_path| (*page, ref_path, *cmp_path))
+        })
+        .map(|(page, ref_path, cmp_path)| {
+            diff_page(page, ref_path, cmp_path, out_dir, file_prefix)
+        })
+        .collect()
+}
+
+fn diff_page(
+    page: u32,
+    ref_path: &Path,
+    cmp_path: &Path,
+    out_dir: &Path,
+    file_prefix: &str,
+) -> io::Result<PageDiff> {
+    let ref_img = open_rgba(ref_path)?;
+    let cmp_img = open_rgba(cmp_path)?;
+
+    let width = ref_img.width().max(cmp_img.width());
+    let height = ref_img.height().max(cmp_img.height());
+
+    let mut diff_img = RgbaImage::new(width, height);
+    let mut changed = 0u64;
+    let mut bbox: Option<(u32, u32, u32, u32)> = None;
+    for y in 0..height {
+        for x in 0..width {
+            let ref_px = pixel_at(&ref_img, x, y);
+            let cmp_px = pixel_at(&cmp_img, x, y);
+            if ref_px == cmp_px {
+                diff_img.put_pixel(x, y, cmp_px);
+                continue;
+            }
+
+            changed += 1;
+            diff_img.put_pixel(x, y, DIFF_TINT);
+            bbox = Some(match bbox {
+                None => (x, y, x, y),
+                Some((min_x, min_y, max_x, max_y)) => {
+                    (min_x.min(x), min_y.min(y), max_x.max(x), max_y.max(y))
+                }
+            });
+        }
+    }
+    let total_pixels = (u64::from(width) * u64::from(height)).max(1);
+    let changed_ratio = changed as f32 / total_pixels as f32;
+
+    if changed > 0 {
+        side_by_side_image(&ref_img, &cmp_img)
+            .save(
+                out_dir
+                    .join(format!("{file_prefix}-page-{page}-side-by-side.png")),
+            )
+            .map_err(io::Error::other)?;
+        diff_img
+            .save(out_dir.join(format!("{file_prefix}-page-{page}-diff.png")))
+            .map_err(io::Error::other)?;
+    }
+
+    Ok(PageDiff { page, changed_ratio, bbox })
+}
+
+fn open_rgba(path: &Path) -> io::Result<RgbaImage> {
+    image::open(path).map(|image| image.to_rgba8()).map_err(io::Error::other)
+}
+
+/// Returns the pixel at `(x, y)`, or transparent black if it falls outside
+/// `img` (which happens when the two pages being diffed have different
+/// dimensions).
+fn pixel_at(img: &RgbaImage, x: u32, y: u32) -> Rgba<u8> {
+    if x < img.width() && y < img.height() {
+        *img.get_pixel(x, y)
+    } else {
+        Rgba([0, 0, 0, 0])
+    }
+}
+
+fn side_by_side_image(ref_img: &RgbaImage, cmp_img: &RgbaImage) -> RgbaImage {
+    let height = ref_img.height().max(cmp_img.height());
+    let width = ref_img.width() + cmp_img.width();
+
+    let mut combined = RgbaImage::new(width, height);
+    for y in 0..height {
+        for x in 0..ref_img.width() {
+            combined.put_pixel(x, y, pixel_at(ref_img, x, y));
+        }
+        for x in 0..cmp_img.width() {
+            combined.put_pixel(ref_img.width() + x, y, pixel_at(cmp_img, x, y));
+        }
+    }
+    combined
+}
@@ -0,0 +1,85 @@
+// Copyright © 2023 Luke Chambers
+// This file is part of Backtrack.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at <http://www.apache.org/licenses/LICENSE-2.0>.
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Parses the leading `//!` comment block of a sample Typst source file for
+//! directives that control how it's run, similar to `compiletest_rs`'s
+//! header parsing. Supported directives:
+//!
+//! - `backtrack-only: <names>` — only run the listed compilers
+//! - `backtrack-ignore: <names>` — skip the listed compilers
+//! - `extra-args: <args>` — extra arguments folded into the compile command
+//! - `expect: mismatch` — the compare document is expected to mismatch
+
+use std::{fs, path::Path};
+
+use anyhow::{Context, Result};
+
+/// The directives parsed from a sample file's leading comment block.
+#[derive(Debug, Default)]
+pub struct Header {
+    /// If set, only these compiler names are run; all others are skipped.
+    only: Option<Vec<String>>,
+    /// These compiler names are skipped even if they'd otherwise run.
+    ignore: Vec<String>,
+    /// Extra arguments folded into the compile command.
+    pub extra_args: Vec<String>,
+    /// Whether this sample is expected to produce a mismatch.
+    pub expect_mismatch: bool,
+}
+
+impl Header {
+    /// Returns whether the compiler named `name` should run, per the
+    /// `backtrack-only`/`backtrack-ignore` directives.
+    pub fn allows(&self, name: &str) -> bool {
+        if let Some(only) = &self.only {
+            if !only.iter().any(|allowed| allowed == name) {
+                return false;
+            }
+        }
+        !self.ignore.iter().any(|ignored| ignored == name)
+    }
+}
+
+/// Parses the leading `//!` comment block of `sample` for directives.
+pub fn parse(sample: impl AsRef<Path>) -> Result<Header> {
+    let contents = fs::read_to_string(sample.as_ref())
+        .context("failed to read the sample file")?;
+
+    let mut header = Header::default();
+    for line in contents.lines() {
+        let Some(directive) = line.trim_start().strip_prefix("//!") else {
+            break;
+        };
+        let Some((key, value)) = directive.split_once(':') else {
+            continue;
+        };
+        let value = value.trim();
+        match key.trim() {
+            "backtrack-only" => {
+                header
+                    .only
+                    .get_or_insert_with(Vec::new)
+                    .extend(value.split(',').map(|name| name.trim().to_owned()));
+            }
+            "backtrack-ignore" => header
+                .ignore
+                .extend(value.split(',').map(|name| name.trim().to_owned())),
+            "extra-args" => header
+                .extra_args
+                .extend(value.split_whitespace().map(ToOwned::to_owned)),
+            "expect" => header.expect_mismatch = value == "mismatch",
+            _ => {}
+        }
+    }
+    Ok(header)
+}
@@ -0,0 +1,60 @@
+// Copyright © 2023 Luke Chambers
+// This file is part of Backtrack.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at <http://www.apache.org/licenses/LICENSE-2.0>.
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! Loads the `--known-mismatches` allowlist: a JSON object mapping compiler
+//! names to the sample names they're documented to mismatch on, e.g.:
+//!
+//! ```json
+//! {
+//!   "v0-7-0": ["layout-regression"],
+//!   "v1-0-0": ["*"]
+//! }
+//! ```
+//!
+//! `"*"` allows the compiler to mismatch on every sample, the same way `*`
+//! selects every compiler for [`CompilersSpec::All`](crate::args::CompilersSpec::All).
+//!
+//! Only JSON and exact compiler names are supported; there's no version
+//! range syntax, so a mismatch that spans several compiler versions needs an
+//! entry per version.
+
+use std::{collections::HashMap, fs, path::Path};
+
+use anyhow::{Context, Result};
+use serde::Deserialize;
+
+/// A documented allowlist of (compiler, sample) pairs that are expected to
+/// mismatch, so they don't fail CI like an undocumented divergence would.
+#[derive(Debug, Default, Deserialize)]
+#[serde(transparent)]
+pub struct KnownMismatches {
+    by_compiler: HashMap<String, Vec<String>>,
+}
+
+impl KnownMismatches {
+    /// Loads an allowlist from a JSON file at `path`.
+    pub fn load(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path)
+            .context("failed to read the known-mismatches file")?;
+        serde_json::from_str(&contents)
+            .context("failed to parse the known-mismatches file")
+    }
+
+    /// Whether `compiler` mismatching on `sample` is documented as an
+    /// expected mismatch rather than a regression.
+    pub fn allows(&self, compiler: &str, sample: &str) -> bool {
+        self.by_compiler.get(compiler).is_some_and(|samples| {
+            samples.iter().any(|allowed| allowed == "*" || allowed == sample)
+        })
+    }
+}
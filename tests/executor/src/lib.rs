@@ -13,59 +13,84 @@
 
 use std::{
     borrow::Cow,
-    collections::HashMap,
+    collections::{HashSet, VecDeque},
     fmt::{self, Display, Formatter},
     fs, io,
     path::{Path, PathBuf},
-    process::{self, Command, Output},
+    process::{self, Command, Output, Stdio},
     result, str,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+    thread,
 };
 
 use anyhow::{bail, Context, Result};
-use args::{Args, Cmd, CompilersSpec};
+use args::{Args, BisectArgs, Cmd, CompareMode, CompilersSpec, Format};
+use diff::PageDiff;
+use known_mismatches::KnownMismatches;
 use log::{debug, error, info, warn};
 use owo_colors::{OwoColorize, Style};
+use report::{JobReport, Outcome, RenderedDiff, Report};
 use terminal_size::{terminal_size, Width};
 use thiserror::Error;
 
 pub mod args;
+mod diff;
+mod header;
+mod known_mismatches;
+mod read2;
+mod report;
 
 pub fn run(args: Args) -> Result<()> {
     fs::create_dir_all(&args.run_dir)
         .context("failed to create the run directory")?;
-    let (op, compile_args, cmp_dir) = match args.cmd {
+    let format = args.format;
+    let (op, compile_args, cmp_base_dir) = match args.cmd {
         Cmd::ExtractCompilers { archive } => {
             return extract_compilers(archive, &args.run_dir)
                 .context("failed to extract the compilers")
         }
+        Cmd::Bisect(bisect_args) => {
+            return bisect(&args.run_dir, bisect_args).context("failed to bisect")
+        }
         Cmd::GenRefs(compile_args) => {
             fs::create_dir_all(&compile_args.ref_dir)
                 .context("failed to create the reference directory")?;
             (Op::GenRefs, compile_args, None)
         }
         Cmd::Test(compile_args) => {
-            let cmp_dir = args.run_dir.join("cmps");
-            fs::create_dir_all(&cmp_dir)
+            if compile_args.bless
+                && matches!(compile_args.compilers, CompilersSpec::All)
+            {
+                bail!(concat!(
+                    "--bless can't be used with `*`;",
+                    " pass the specific compilers you want to bless",
+                ));
+            }
+            let cmp_base_dir = args.run_dir.join("cmps");
+            fs::create_dir_all(&cmp_base_dir)
                 .context("failed to create the compare directory")?;
-            (Op::Test, compile_args, Some(cmp_dir))
+            (Op::Test, compile_args, Some(cmp_base_dir))
         }
     };
 
+    let known_mismatches = match &compile_args.known_mismatches {
+        Some(path) => KnownMismatches::load(path)
+            .context("failed to load the known-mismatches file")?,
+        None => KnownMismatches::default(),
+    };
+
     let compiler_dir = args.run_dir.join("compilers");
-    let compilers: Vec<_> = match compile_args.compilers {
+    let compilers: Vec<_> = match &compile_args.compilers {
         CompilersSpec::All => fs::read_dir(&compiler_dir)
             .context("failed to read the compiler directory")?
             .filter_map(Result::ok)
             .filter_map(|entry| {
                 entry.file_name().into_string().ok().and_then(|name| {
                     if name.starts_with('v') {
-                        let compiler = Compiler::new(
-                            name,
-                            entry.path(),
-                            &compile_args.ref_dir,
-                            cmp_dir.as_ref(),
-                        );
-                        Some(compiler)
+                        Some(Compiler::new(name, entry.path()))
                     } else {
                         None
                     }
@@ -73,20 +98,77 @@ pub fn run(args: Args) -> Result<()> {
             })
             .collect(),
         CompilersSpec::Specific(names) => names
-            .into_iter()
+            .iter()
             .map(|name| {
-                let path = compiler_dir.join(&name);
-                Compiler::new(
-                    name,
-                    path,
-                    &compile_args.ref_dir,
-                    cmp_dir.as_ref(),
-                )
+                let path = compiler_dir.join(name);
+                Compiler::new(name.clone(), path)
             })
             .collect(),
     };
     debug!("Collected compilers: {compilers:?}");
 
+    let samples = discover_samples(&compile_args.sample_dir)
+        .context("failed to discover the sample files")?;
+    if samples.is_empty() {
+        bail!(
+            "no `.typ` sample files were found in {}",
+            compile_args.sample_dir.display(),
+        );
+    }
+
+    let mut jobs = Vec::new();
+    for sample in &samples {
+        let header = header::parse(&sample.path).with_context(|| {
+            format!(
+                "failed to parse the test directives for sample \"{}\"",
+                sample.name,
+            )
+        })?;
+
+        let ref_dir = compile_args.ref_dir.join(&sample.name);
+        fs::create_dir_all(&ref_dir).context(
+            "failed to create a sample's reference directory",
+        )?;
+        let cmp_dir = cmp_base_dir
+            .as_ref()
+            .map(|cmp_base_dir| cmp_base_dir.join(&sample.name))
+            .map(|cmp_dir| -> Result<_> {
+                fs::create_dir_all(&cmp_dir).context(
+                    "failed to create a sample's compare directory",
+                )?;
+                Ok(cmp_dir)
+            })
+            .transpose()?;
+
+        for compiler in &compilers {
+            if !header.allows(&compiler.name) {
+                debug!(
+                    "Skipping {compiler} for sample \"{}\" due to a \
+                     backtrack-only/-ignore directive.",
+                    sample.name,
+                );
+                continue;
+            }
+
+            let output_name = match compile_args.compare_mode {
+                CompareMode::Rendered => format!("{}-{{p}}.png", compiler.name),
+                CompareMode::Digest | CompareMode::Normalized => {
+                    format!("{}.pdf", compiler.name)
+                }
+            };
+            jobs.push(Job {
+                sample_name: sample.name.clone(),
+                sample_path: sample.path.clone(),
+                compiler: compiler.clone(),
+                ref_path: ref_dir.join(&output_name),
+                cmp_path: cmp_dir.as_ref().map(|cmp_dir| cmp_dir.join(&output_name)),
+                extra_args: header.extra_args.clone(),
+                expect_mismatch: header.expect_mismatch,
+                compare_mode: compile_args.compare_mode,
+            });
+        }
+    }
+
     let separator_width = if let Some((Width(width), _)) = terminal_size() {
         width - 7
     } else {
@@ -94,107 +176,540 @@ pub fn run(args: Args) -> Result<()> {
     };
     let separator = "-".repeat(separator_width.into());
 
-    let mut results = HashMap::with_capacity(compilers.len());
-    let mut success = true;
-    let mut mismatches = false;
-    let mut longest_name_len = 0;
-    for compiler in compilers {
-        info!("{separator}");
+    let longest_name_len =
+        compilers.iter().map(|compiler| compiler.name.len()).max().unwrap_or(0);
+
+    let queue = Mutex::new(jobs.into_iter().collect::<VecDeque<_>>());
+    let reports: Mutex<Vec<JobReport>> = Mutex::new(Vec::new());
+    let success = AtomicBool::new(true);
+    let mismatches = AtomicBool::new(false);
+    // Held for the duration of a single job's log lines so output from
+    // different workers doesn't get interleaved.
+    let log_lock = Mutex::new(());
 
-        let name_len = compiler.name.len();
-        if name_len > longest_name_len {
-            longest_name_len = name_len;
+    let worker_count = compile_args.jobs.max(1);
+    thread::scope(|scope| {
+        for _ in 0..worker_count {
+            scope.spawn(|| loop {
+                let job = queue.lock().unwrap().pop_front();
+                let Some(job) = job else { break };
+
+                let report = run_job(
+                    job,
+                    op,
+                    &compile_args.project_root,
+                    compile_args.bless,
+                    &known_mismatches,
+                    &separator,
+                    &log_lock,
+                );
+                if report.is_failure() {
+                    success.store(false, Ordering::Relaxed);
+                }
+                if report.is_unexpected_mismatch() {
+                    mismatches.store(true, Ordering::Relaxed);
+                }
+                reports.lock().unwrap().push(report);
+            });
         }
+    });
 
-        info!("Running {op:?} for {compiler}.");
-        if let Err(err) = compiler.set_executable() {
-            error!("Failed to set the executable's permissions: {err}");
-            results.insert(
-                compiler.into_name(),
-                OpResult::Err("permission setting"),
-            );
-            success = false;
-            continue;
+    let mut jobs = reports.into_inner().unwrap();
+    jobs.sort_by(|a, b| {
+        a.sample.cmp(&b.sample).then_with(|| a.compiler.cmp(&b.compiler))
+    });
+    let success = success.into_inner();
+    let mismatches = mismatches.into_inner();
+
+    match format {
+        Format::Human => {
+            info!("{separator}");
+            if success {
+                info!("{}", "TEST SUCCESS".bright_green().bold());
+            } else {
+                info!("{}", "TEST FAILURE".bright_red().bold());
+            };
+            let mut sample_names: Vec<_> =
+                jobs.iter().map(|job| job.sample.clone()).collect();
+            sample_names.dedup();
+            for sample_name in sample_names {
+                info!("{separator}");
+                let sample_jobs: Vec<_> =
+                    jobs.iter().filter(|job| job.sample == sample_name).collect();
+                let failed =
+                    sample_jobs.iter().filter(|job| job.is_failure()).count();
+                info!(
+                    "{} ({failed}/{} failed):",
+                    sample_name.bold(),
+                    sample_jobs.len(),
+                );
+                for job in sample_jobs {
+                    let padded_name =
+                        format!("{:longest_name_len$}", job.compiler);
+                    let (desc, style) = describe_outcome(&job.outcome);
+                    info!("  {padded_name} | {}", desc.style(style));
+                }
+            }
+            if mismatches {
+                info!("{separator}");
+                info!(
+                    "You can find the compiled documents from the failed \
+                     tests in {}.",
+                    cmp_base_dir.unwrap().display(),
+                );
+            }
         }
-        if let Ok(version) = compiler.reported_version() {
-            info!("The compiler reports itself as \"{version}\".");
-        } else {
-            warn!(concat!(
-                "Failed to get the compiler version.",
-                " This is probably just an old (pre-3/21) compiler.",
-            ));
+        Format::Json => {
+            let report = Report { success, jobs };
+            serde_json::to_writer_pretty(io::stdout(), &report)
+                .context("failed to write the JSON report")?;
+            println!();
         }
+    }
 
-        let result = match op {
-            Op::GenRefs => {
-                let result = compiler
-                    .gen_ref(&compile_args.sample, &compile_args.project_root);
-                if let Err(err) = result {
-                    error!("Failed to generate the reference document: {err}");
-                    err.log_unsuccessful_exit();
-                    success = false;
-                    OpResult::Err("reference generation")
-                } else {
-                    info!("Successfully generated the reference document.");
-                    OpResult::Ok
+    if !success {
+        process::exit(1);
+    }
+    Ok(())
+}
+
+/// Describes an [`Outcome`] the way the human-readable table presents it:
+/// a short label and the color to print it with.
+fn describe_outcome(outcome: &Outcome) -> (Cow<'static, str>, Style) {
+    match outcome {
+        Outcome::Ok => (Cow::Borrowed("OK"), Style::new().bright_green()),
+        Outcome::Err { stage } => (
+            Cow::Owned(format!("Error during {stage}")),
+            Style::new().red(),
+        ),
+        Outcome::Mismatch { expected: false, mode, .. } => (
+            Cow::Owned(format!("Mismatch ({mode})")),
+            Style::new().bright_red(),
+        ),
+        Outcome::Mismatch { expected: true, mode, .. } => (
+            Cow::Owned(format!("Mismatch ({mode}, expected)")),
+            Style::new().yellow(),
+        ),
+        Outcome::ExpectedMismatch { mode, .. } => (
+            Cow::Owned(format!("Mismatch ({mode}, known)")),
+            Style::new().yellow(),
+        ),
+        Outcome::Blessed => {
+            (Cow::Borrowed("Blessed"), Style::new().bright_yellow())
+        }
+    }
+}
+
+/// Buffers a job's log lines so the whole job can be run without holding
+/// `log_lock`, and the lines can still be flushed atomically afterwards (via
+/// [`JobLog::flush`]) so they don't interleave with another job's output.
+struct JobLog {
+    lines: Vec<(log::Level, String)>,
+}
+
+impl JobLog {
+    fn new() -> Self {
+        Self { lines: Vec::new() }
+    }
+
+    fn info(&mut self, line: impl Into<String>) {
+        self.lines.push((log::Level::Info, line.into()));
+    }
+
+    fn warn(&mut self, line: impl Into<String>) {
+        self.lines.push((log::Level::Warn, line.into()));
+    }
+
+    fn error(&mut self, line: impl Into<String>) {
+        self.lines.push((log::Level::Error, line.into()));
+    }
+
+    /// Prints every buffered line in order, holding `log_lock` only for the
+    /// duration of the printing.
+    fn flush(self, log_lock: &Mutex<()>) {
+        let _guard = log_lock.lock().unwrap();
+        for (level, line) in self.lines {
+            match level {
+                log::Level::Error => error!("{line}"),
+                log::Level::Warn => warn!("{line}"),
+                _ => info!("{line}"),
+            }
+        }
+    }
+}
+
+/// Runs `op` for a single `job` (one sample compiled by one compiler). The
+/// actual compiling/testing is done without holding `log_lock`, so multiple
+/// jobs can run concurrently; the job's log lines are buffered in a
+/// [`JobLog`] and only flushed (under `log_lock`, so they don't interleave
+/// with another job's output) once the job is done.
+fn run_job(
+    job: Job,
+    op: Op,
+    project_root: &Path,
+    bless: bool,
+    known_mismatches: &KnownMismatches,
+    separator: &str,
+    log_lock: &Mutex<()>,
+) -> JobReport {
+    let mut log = JobLog::new();
+    log.info(separator.to_owned());
+    log.info(format!(
+        "Running {op:?} for {} on sample \"{}\".",
+        job.compiler, job.sample_name,
+    ));
+    let op_name = match op {
+        Op::GenRefs => "gen_refs",
+        Op::Test => "test",
+    };
+
+    if let Err(err) = job.compiler.set_executable() {
+        log.error(format!("Failed to set the executable's permissions: {err}"));
+        log.flush(log_lock);
+        return JobReport {
+            sample: job.sample_name,
+            compiler: job.compiler.into_name(),
+            op: op_name,
+            reported_version: None,
+            outcome: Outcome::Err { stage: "permission setting".to_owned() },
+            ref_digest: None,
+            cmp_digest: None,
+            exit_code: None,
+            stderr: String::new(),
+        };
+    }
+
+    let reported_version = job.compiler.reported_version().ok();
+    match &reported_version {
+        Some(version) => {
+            log.info(format!("The compiler reports itself as \"{version}\"."));
+        }
+        None => log.warn(concat!(
+            "Failed to get the compiler version.",
+            " This is probably just an old (pre-3/21) compiler.",
+        )),
+    }
+
+    let (outcome, ref_digest, cmp_digest, exit_code, stderr) = match op {
+        Op::GenRefs => {
+            let result = job.compiler.gen_ref(
+                &job.sample_path,
+                &job.ref_path,
+                project_root,
+                &job.extra_args,
+            );
+            match result {
+                Ok(_) => {
+                    log.info("Successfully generated the reference document.");
+                    (Outcome::Ok, None, None, Some(0), String::new())
+                }
+                Err(err) => {
+                    log.error(format!(
+                        "Failed to generate the reference document: {err}",
+                    ));
+                    err.log_unsuccessful_exit(&mut log);
+                    let (code, stderr) = err.exit_info();
+                    (
+                        Outcome::Err { stage: "reference generation".to_owned() },
+                        None,
+                        None,
+                        code,
+                        stderr,
+                    )
                 }
             }
-            Op::Test => {
-                let result = compiler
-                    .test(&compile_args.sample, &compile_args.project_root);
-                match result {
-                    Ok(_) => {
-                        info!("The test passed.");
-                        OpResult::Ok
+        }
+        Op::Test => {
+            let cmp_path = job
+                .cmp_path
+                .as_ref()
+                .expect("a Test job must have a compare path");
+            let result = job.compiler.test(
+                &job.sample_path,
+                &job.ref_path,
+                cmp_path,
+                project_root,
+                &job.extra_args,
+                job.compare_mode,
+            );
+            match result {
+                Ok(_)
+                    if known_mismatches
+                        .allows(&job.compiler.name, &job.sample_name) =>
+                {
+                    log.error(concat!(
+                        "The test passed, but the known-mismatches file",
+                        " lists this compiler as expected to mismatch on",
+                        " this sample; the allowlist entry is stale.",
+                    ));
+                    (
+                        Outcome::Err {
+                            stage: "stale known-mismatch entry".to_owned(),
+                        },
+                        None,
+                        None,
+                        Some(0),
+                        String::new(),
+                    )
+                }
+                Ok(_) => {
+                    log.info("The test passed.");
+                    (Outcome::Ok, None, None, Some(0), String::new())
+                }
+                Err(TestError::CompileFailed(err)) => {
+                    log.error(format!(
+                        "Failed to compile the compare document: {err}",
+                    ));
+                    err.log_unsuccessful_exit(&mut log);
+                    let (code, stderr) = err.exit_info();
+                    (
+                        Outcome::Err { stage: "compare compilation".to_owned() },
+                        None,
+                        None,
+                        code,
+                        stderr,
+                    )
+                }
+                Err(TestError::Mismatch { mode, ref_digest, cmp_digest }) => {
+                    if known_mismatches.allows(&job.compiler.name, &job.sample_name)
+                    {
+                        log.info(concat!(
+                            "The documents don't match, but this is",
+                            " documented in the known-mismatches file.",
+                        ));
+                        (
+                            Outcome::ExpectedMismatch {
+                                mode: mode.as_str(),
+                                rendered_diff: None,
+                            },
+                            Some(ref_digest),
+                            Some(cmp_digest),
+                            Some(0),
+                            String::new(),
+                        )
+                    } else if job.expect_mismatch {
+                        log.info(concat!(
+                            "The documents don't match, but this was",
+                            " expected per the sample's `expect` directive.",
+                        ));
+                        (
+                            Outcome::Mismatch {
+                                expected: true,
+                                mode: mode.as_str(),
+                                rendered_diff: None,
+                            },
+                            Some(ref_digest),
+                            Some(cmp_digest),
+                            Some(0),
+                            String::new(),
+                        )
+                    } else if bless {
+                        match job.compiler.bless(
+                            &job.ref_path,
+                            cmp_path,
+                            job.compare_mode,
+                        ) {
+                            Ok(_) => {
+                                log.info(concat!(
+                                    "The test failed, but the reference",
+                                    " document was blessed with the",
+                                    " compare document.",
+                                ));
+                                (
+                                    Outcome::Blessed,
+                                    Some(ref_digest),
+                                    Some(cmp_digest),
+                                    Some(0),
+                                    String::new(),
+                                )
+                            }
+                            Err(err) => {
+                                log.error(format!(
+                                    "Failed to bless the reference \
+                                     document: {err}",
+                                ));
+                                (
+                                    Outcome::Err { stage: "blessing".to_owned() },
+                                    Some(ref_digest),
+                                    Some(cmp_digest),
+                                    Some(0),
+                                    String::new(),
+                                )
+                            }
+                        }
+                    } else {
+                        log.error(format!(
+                            "The test failed under {mode} comparison.",
+                        ));
+                        log.error(format!("Reference digest: {ref_digest}"));
+                        log.error(format!("Compare digest: {cmp_digest}"));
+                        (
+                            Outcome::Mismatch {
+                                expected: false,
+                                mode: mode.as_str(),
+                                rendered_diff: None,
+                            },
+                            Some(ref_digest),
+                            Some(cmp_digest),
+                            Some(0),
+                            String::new(),
+                        )
                     }
-                    Err(TestError::CompileFailed(err)) => {
-                        error!("Failed to compile the compare document: {err}");
-                        err.log_unsuccessful_exit();
-                        success = false;
-                        OpResult::Err("compare compilation")
+                }
+                Err(TestError::RenderedMismatch { pages, missing_pages, extra_pages }) => {
+                    for page_diff in &pages {
+                        let bbox = page_diff.bbox.map_or_else(
+                            String::new,
+                            |(x0, y0, x1, y1)| {
+                                format!(" (bbox: ({x0}, {y0}) to ({x1}, {y1}))")
+                            },
+                        );
+                        log.info(format!(
+                            "Page {}: {:.2}% of pixels differ{bbox}.",
+                            page_diff.page,
+                            page_diff.changed_ratio * 100.0,
+                        ));
                     }
-                    Err(TestError::Mismatch { ref_digest, cmp_digest }) => {
-                        error!("The test failed.",);
-                        error!("Reference digest: {ref_digest}");
-                        error!("Compare digest: {cmp_digest}");
-                        success = false;
-                        mismatches = true;
-                        OpResult::Mismatch
+                    if !missing_pages.is_empty() {
+                        log.warn(format!(
+                            "The compare document is missing pages: \
+                             {missing_pages:?}",
+                        ));
                     }
-                    Err(err) => {
-                        error!("Failed to run the test: {err}");
-                        success = false;
-                        OpResult::Err("test")
+                    if !extra_pages.is_empty() {
+                        log.warn(format!(
+                            "The compare document has extra pages: \
+                             {extra_pages:?}",
+                        ));
+                    }
+
+                    if known_mismatches.allows(&job.compiler.name, &job.sample_name)
+                    {
+                        log.info(concat!(
+                            "The documents don't match, but this is",
+                            " documented in the known-mismatches file.",
+                        ));
+                        (
+                            Outcome::ExpectedMismatch {
+                                mode: "rendered",
+                                rendered_diff: Some(RenderedDiff {
+                                    pages,
+                                    missing_pages,
+                                    extra_pages,
+                                }),
+                            },
+                            None,
+                            None,
+                            Some(0),
+                            String::new(),
+                        )
+                    } else if job.expect_mismatch {
+                        log.info(concat!(
+                            "The documents don't match, but this was",
+                            " expected per the sample's `expect` directive.",
+                        ));
+                        (
+                            Outcome::Mismatch {
+                                expected: true,
+                                mode: "rendered",
+                                rendered_diff: Some(RenderedDiff {
+                                    pages,
+                                    missing_pages,
+                                    extra_pages,
+                                }),
+                            },
+                            None,
+                            None,
+                            Some(0),
+                            String::new(),
+                        )
+                    } else if bless {
+                        match job.compiler.bless(
+                            &job.ref_path,
+                            cmp_path,
+                            job.compare_mode,
+                        ) {
+                            Ok(_) => {
+                                log.info(concat!(
+                                    "The test failed, but the reference",
+                                    " document was blessed with the",
+                                    " compare document.",
+                                ));
+                                (Outcome::Blessed, None, None, Some(0), String::new())
+                            }
+                            Err(err) => {
+                                log.error(format!(
+                                    "Failed to bless the reference \
+                                     document: {err}",
+                                ));
+                                (
+                                    Outcome::Err { stage: "blessing".to_owned() },
+                                    None,
+                                    None,
+                                    Some(0),
+                                    String::new(),
+                                )
+                            }
+                        }
+                    } else {
+                        log.error(
+                            "The test failed; see the per-page diff above.",
+                        );
+                        (
+                            Outcome::Mismatch {
+                                expected: false,
+                                mode: "rendered",
+                                rendered_diff: Some(RenderedDiff {
+                                    pages,
+                                    missing_pages,
+                                    extra_pages,
+                                }),
+                            },
+                            None,
+                            None,
+                            Some(0),
+                            String::new(),
+                        )
                     }
                 }
+                Err(TestError::DiffFailed(err)) => {
+                    log.error(format!(
+                        "Failed to compute the rendered page diff: {err}",
+                    ));
+                    (
+                        Outcome::Err { stage: "page diff".to_owned() },
+                        None,
+                        None,
+                        None,
+                        String::new(),
+                    )
+                }
+                Err(err) => {
+                    log.error(format!("Failed to run the test: {err}"));
+                    (
+                        Outcome::Err { stage: "test".to_owned() },
+                        None,
+                        None,
+                        None,
+                        String::new(),
+                    )
+                }
             }
-        };
-        results.insert(compiler.into_name(), result);
-    }
-
-    info!("{separator}");
-    if success {
-        info!("{}", "TEST SUCCESS".bright_green().bold());
-    } else {
-        info!("{}", "TEST FAILURE".bright_red().bold());
+        }
     };
-    info!("{separator}");
-    for (name, result) in results {
-        let padded_name = format!("{name:longest_name_len$}");
-        let (result_desc, result_style) = result.fmt();
-        info!("{padded_name} | {}", result_desc.style(result_style));
-    }
-    if mismatches {
-        info!(
-            "You can find the compiled documents from the failed tests in {}.",
-            cmp_dir.unwrap().display(),
-        );
-    }
 
-    if !success {
-        process::exit(1);
+    log.flush(log_lock);
+    JobReport {
+        sample: job.sample_name,
+        compiler: job.compiler.into_name(),
+        op: op_name,
+        reported_version,
+        outcome,
+        ref_digest,
+        cmp_digest,
+        exit_code,
+        stderr,
     }
-    Ok(())
 }
 
 fn extract_compilers<F, T>(from: F, to: T) -> Result<()>
@@ -209,62 +724,262 @@ where
     Ok(())
 }
 
-#[derive(Debug)]
+/// Finds the first available compiler whose output for `args.sample`
+/// diverges from `args.baseline`.
+fn bisect(run_dir: &Path, args: BisectArgs) -> Result<()> {
+    if args.compare_mode == CompareMode::Rendered {
+        bail!(
+            "--compare-mode rendered isn't supported for bisect: the \
+             baseline is a single known-good document, not a {{p}} \
+             page-number template",
+        );
+    }
+
+    let compiler_dir = run_dir.join("compilers");
+    let mut compilers: Vec<_> = fs::read_dir(&compiler_dir)
+        .context("failed to read the compiler directory")?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            entry.file_name().into_string().ok().and_then(|name| {
+                if name.starts_with('v') {
+                    Some(Compiler::new(name, entry.path()))
+                } else {
+                    None
+                }
+            })
+        })
+        .collect();
+    compilers.sort_by_key(|compiler| version_key(&compiler.name));
+    if compilers.is_empty() {
+        bail!("no compilers were found in {}", compiler_dir.display());
+    }
+
+    let baseline_digest = digest_document(&args.baseline, args.compare_mode)
+        .context("failed to read the baseline document")?;
+
+    let cmp_dir = run_dir.join("bisect");
+    fs::create_dir_all(&cmp_dir)
+        .context("failed to create the bisect directory")?;
+
+    if args.verify {
+        verify_bisect(&compilers, &args, &baseline_digest, &cmp_dir)
+    } else {
+        binary_search_bisect(&compilers, &args, &baseline_digest, &cmp_dir)
+    }
+}
+
+/// Compiles `args.sample` with `compiler` into `cmp_dir` and digests the
+/// result the way `args.compare_mode` says to.
+fn compile_and_digest(
+    compiler: &Compiler,
+    args: &BisectArgs,
+    cmp_dir: &Path,
+) -> Result<String> {
+    compiler
+        .set_executable()
+        .context("failed to set the executable's permissions")?;
+
+    let output_name = match args.compare_mode {
+        CompareMode::Rendered => format!("{}-{{p}}.png", compiler.name),
+        CompareMode::Digest | CompareMode::Normalized => {
+            format!("{}.pdf", compiler.name)
+        }
+    };
+    let cmp_path = cmp_dir.join(output_name);
+    compiler
+        .gen_ref(&args.sample, &cmp_path, &args.project_root, &[])
+        .with_context(|| format!("failed to compile with {compiler}"))?;
+    digest_document(&cmp_path, args.compare_mode)
+        .with_context(|| format!("failed to read the document compiled by {compiler}"))
+}
+
+/// Binary-searches `compilers` (assumed version-sorted) for the boundary
+/// where the output stops matching `baseline_digest`. This assumes the
+/// output diverges from the baseline exactly once across the whole range;
+/// if that assumption doesn't hold, `--verify` should be used instead.
+fn binary_search_bisect(
+    compilers: &[Compiler],
+    args: &BisectArgs,
+    baseline_digest: &str,
+    cmp_dir: &Path,
+) -> Result<()> {
+    info!(
+        "Binary-searching {} compilers for the version that introduced the \
+         regression.",
+        compilers.len(),
+    );
+
+    let first_digest = compile_and_digest(&compilers[0], args, cmp_dir)?;
+    if first_digest != baseline_digest {
+        bail!(
+            "the earliest available compiler ({}) already diverges from \
+             the baseline; there's no last-good version to find",
+            compilers[0],
+        );
+    }
+
+    let mut low = 0;
+    let mut high = compilers.len() - 1;
+    let last_digest = compile_and_digest(&compilers[high], args, cmp_dir)?;
+    if last_digest == baseline_digest {
+        info!(
+            "All {} compilers match the baseline; no regression was found.",
+            compilers.len(),
+        );
+        return Ok(());
+    }
+
+    while high - low > 1 {
+        let mid = low + (high - low) / 2;
+        let digest = compile_and_digest(&compilers[mid], args, cmp_dir)?;
+        if digest == baseline_digest {
+            info!("{} matches the baseline.", compilers[mid]);
+            low = mid;
+        } else {
+            info!("{} diverges from the baseline.", compilers[mid]);
+            high = mid;
+        }
+    }
+
+    info!("Last good version: {}", compilers[low]);
+    info!("First bad version: {}", compilers[high]);
+    Ok(())
+}
+
+/// Linearly scans every compiler in `compilers` against `baseline_digest`,
+/// reporting every transition. Unlike [`binary_search_bisect`], this handles
+/// a history where the output diverges from and returns to the baseline more
+/// than once.
+fn verify_bisect(
+    compilers: &[Compiler],
+    args: &BisectArgs,
+    baseline_digest: &str,
+    cmp_dir: &Path,
+) -> Result<()> {
+    info!(
+        "Linearly scanning all {} compilers against the baseline.",
+        compilers.len(),
+    );
+
+    let mut matched = Vec::with_capacity(compilers.len());
+    for compiler in compilers {
+        let digest = compile_and_digest(compiler, args, cmp_dir)?;
+        matched.push(digest == baseline_digest);
+    }
+
+    let mut any_transition = false;
+    for i in 1..compilers.len() {
+        if matched[i] == matched[i - 1] {
+            continue;
+        }
+        any_transition = true;
+        if matched[i] {
+            info!(
+                "Transition back to matching the baseline between {} (bad) \
+                 and {} (good).",
+                compilers[i - 1],
+                compilers[i],
+            );
+        } else {
+            info!(
+                "Transition away from the baseline between {} (good) and \
+                 {} (bad).",
+                compilers[i - 1],
+                compilers[i],
+            );
+        }
+    }
+
+    if !any_transition {
+        if matched[0] {
+            info!(
+                "All {} compilers match the baseline; no regression was \
+                 found.",
+                compilers.len(),
+            );
+        } else {
+            warn!(
+                "All {} compilers diverge from the baseline, including the \
+                 earliest.",
+                compilers.len(),
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Gives `v*` compiler directory names (e.g. `v2023-03-21`, `v0-7-0`,
+/// `v1-2-0`) a total chronological order, reusing the era boundaries
+/// [`CompilerArgLayout::from_compiler_name`] already distinguishes.
+fn version_key(name: &str) -> (u8, Vec<u32>) {
+    let era = match CompilerArgLayout::from_compiler_name(name) {
+        CompilerArgLayout::NoSubcommand => 0,
+        CompilerArgLayout::SubcommandAfterRoot => 1,
+        CompilerArgLayout::SubcommandBeforeRoot => 2,
+    };
+    let parts = name
+        .trim_start_matches('v')
+        .split('-')
+        .map(|part| part.parse().unwrap_or(0))
+        .collect();
+    (era, parts)
+}
+
+#[derive(Clone, Copy, Debug)]
 enum Op {
     GenRefs,
     Test,
 }
 
-enum OpResult {
-    Ok,
-    Err(&'static str),
-    Mismatch,
+/// A sample Typst source file discovered inside `CompileArgs::sample_dir`.
+#[derive(Clone, Debug)]
+struct Sample {
+    name: String,
+    path: PathBuf,
 }
 
-impl OpResult {
-    pub fn fmt(&self) -> (Cow<'static, str>, Style) {
-        match self {
-            Self::Ok => (Cow::Borrowed("OK"), Style::new().bright_green()),
-            Self::Err(stage) => (
-                Cow::Owned(format!("Error during {stage}")),
-                Style::new().red(),
-            ),
-            Self::Mismatch => {
-                (Cow::Borrowed("Mismatch"), Style::new().bright_red())
+/// Finds every `.typ` file directly inside `dir`, sorted by name so runs are
+/// reproducible.
+fn discover_samples(dir: &Path) -> Result<Vec<Sample>> {
+    let mut samples: Vec<_> = fs::read_dir(dir)
+        .context("failed to read the sample directory")?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let path = entry.path();
+            if path.extension().and_then(|ext| ext.to_str()) != Some("typ") {
+                return None;
             }
-        }
-    }
+            let name = path.file_stem()?.to_str()?.to_owned();
+            Some(Sample { name, path })
+        })
+        .collect();
+    samples.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(samples)
 }
 
-#[derive(Debug)]
+/// A single compiler run for a single sample.
+struct Job {
+    sample_name: String,
+    sample_path: PathBuf,
+    compiler: Compiler,
+    ref_path: PathBuf,
+    cmp_path: Option<PathBuf>,
+    extra_args: Vec<String>,
+    expect_mismatch: bool,
+    compare_mode: CompareMode,
+}
+
+#[derive(Clone, Debug)]
 struct Compiler {
     name: String,
     path: PathBuf,
-    ref_path: PathBuf,
-    cmp_path: Option<PathBuf>,
     arg_layout: CompilerArgLayout,
 }
 
 impl Compiler {
-    pub fn new<R, C>(
-        name: String,
-        path: PathBuf,
-        ref_dir: R,
-        cmp_dir: Option<C>,
-    ) -> Self
-    where
-        R: AsRef<Path>,
-        C: AsRef<Path>,
-    {
-        let pdf = Path::new(&name).with_extension("pdf");
+    pub fn new(name: String, path: PathBuf) -> Self {
         let arg_layout = CompilerArgLayout::from_compiler_name(&name);
-        Self {
-            name,
-            path,
-            ref_path: ref_dir.as_ref().join(&pdf),
-            cmp_path: cmp_dir.map(|cmp_dir| cmp_dir.as_ref().join(&pdf)),
-            arg_layout,
-        }
+        Self { name, path, arg_layout }
     }
 
     pub fn into_name(self) -> String {
@@ -304,42 +1019,100 @@ impl Compiler {
         }
     }
 
-    pub fn gen_ref<S, R>(
+    pub fn gen_ref<S, O, R>(
         &self,
         sample: S,
+        ref_path: O,
         project_root: R,
+        extra_args: &[String],
     ) -> result::Result<(), CompileError>
     where
         S: AsRef<Path>,
+        O: AsRef<Path>,
         R: AsRef<Path>,
     {
-        self.compile(sample, &self.ref_path, project_root)
+        self.compile(sample, ref_path, project_root, extra_args)
     }
 
     pub fn test<S, R>(
         &self,
         sample: S,
+        ref_path: &Path,
+        cmp_path: &Path,
         project_root: R,
+        extra_args: &[String],
+        compare_mode: CompareMode,
     ) -> result::Result<(), TestError>
     where
         S: AsRef<Path>,
         R: AsRef<Path>,
     {
-        let cmp_path =
-            self.cmp_path.as_ref().expect("this compiler has no compare path");
-        self.compile(sample, cmp_path, project_root)
+        self.compile(sample, cmp_path, project_root, extra_args)
             .map_err(TestError::from)?;
-        let ref_doc =
-            fs::read(&self.ref_path).map_err(TestError::RefReadFailed)?;
-        let cmp_doc = fs::read(cmp_path).map_err(TestError::CmpReadFailed)?;
 
-        let ref_digest = sha256::digest(ref_doc);
-        let cmp_digest = sha256::digest(cmp_doc);
+        let ref_digest = digest_document(ref_path, compare_mode)
+            .map_err(TestError::RefReadFailed)?;
+        let cmp_digest = digest_document(cmp_path, compare_mode)
+            .map_err(TestError::CmpReadFailed)?;
         if ref_digest == cmp_digest {
-            Ok(())
-        } else {
-            Err(TestError::Mismatch { ref_digest, cmp_digest })
+            return Ok(());
+        }
+
+        if compare_mode == CompareMode::Rendered {
+            let (pages, missing_pages, extra_pages) =
+                rendered_diff(ref_path, cmp_path).map_err(TestError::DiffFailed)?;
+            // The raw PNG bytes differing doesn't necessarily mean the
+            // rendered pages actually look different (e.g. different
+            // bundled image-encoder settings across Typst versions) --
+            // that's the whole point of comparing renders instead of bytes,
+            // so only report a mismatch if the page-by-page pixel diff
+            // actually found one.
+            let unchanged = missing_pages.is_empty()
+                && extra_pages.is_empty()
+                && pages.iter().all(|page| page.changed_ratio == 0.0);
+            return if unchanged {
+                Ok(())
+            } else {
+                Err(TestError::RenderedMismatch { pages, missing_pages, extra_pages })
+            };
+        }
+        Err(TestError::Mismatch { mode: compare_mode, ref_digest, cmp_digest })
+    }
+
+    /// Overwrites the reference document with the compare document produced
+    /// by the last [`test`](Self::test) call.
+    pub fn bless(
+        &self,
+        ref_path: &Path,
+        cmp_path: &Path,
+        compare_mode: CompareMode,
+    ) -> io::Result<()> {
+        match compare_mode {
+            CompareMode::Digest | CompareMode::Normalized => {
+                fs::copy(cmp_path, ref_path)?;
+            }
+            CompareMode::Rendered => {
+                let ref_pages = rendered_pages_numbered(ref_path)?;
+                let cmp_pages = rendered_pages_numbered(cmp_path)?;
+                let cmp_page_numbers: HashSet<u32> =
+                    cmp_pages.iter().map(|(page, _)| *page).collect();
+
+                // Remove any reference pages the compare document no longer
+                // has, then copy over every compare page (adding any the
+                // reference document didn't have yet), so the reference
+                // page set ends up exactly matching the compare page set
+                // instead of just their shared prefix.
+                for (page, ref_page) in &ref_pages {
+                    if !cmp_page_numbers.contains(page) {
+                        fs::remove_file(ref_page)?;
+                    }
+                }
+                for (page, cmp_page) in cmp_pages {
+                    fs::copy(cmp_page, rendered_page_path(ref_path, page))?;
+                }
+            }
         }
+        Ok(())
     }
 
     fn compile<I, O, R>(
@@ -347,6 +1120,7 @@ impl Compiler {
         input: I,
         output: O,
         project_root: R,
+        extra_args: &[String],
     ) -> result::Result<(), CompileError>
     where
         I: AsRef<Path>,
@@ -357,6 +1131,7 @@ impl Compiler {
             .run(|cmd| {
                 self.arg_layout
                     .cfg_cmd(cmd, project_root)
+                    .args(extra_args)
                     .arg(input.as_ref())
                     .arg(output.as_ref())
             })
@@ -376,7 +1151,9 @@ impl Compiler {
         C: FnOnce(&mut Command) -> &mut Command,
     {
         let mut cmd = Command::new(&self.path);
-        cfg_cmd(&mut cmd).output()
+        cfg_cmd(&mut cmd).stdout(Stdio::piped()).stderr(Stdio::piped());
+        let child = cmd.spawn()?;
+        read2::read2_abbreviated(child)
     }
 }
 
@@ -386,7 +1163,180 @@ impl Display for Compiler {
     }
 }
 
-#[derive(Debug)]
+/// Hashes the document at `path` the way `compare_mode` says to.
+fn digest_document(path: &Path, compare_mode: CompareMode) -> io::Result<String> {
+    match compare_mode {
+        CompareMode::Digest => Ok(sha256::digest(fs::read(path)?)),
+        CompareMode::Normalized => {
+            Ok(sha256::digest(normalize_pdf(fs::read(path)?)))
+        }
+        CompareMode::Rendered => {
+            let mut pages = Vec::new();
+            for page in rendered_pages(path)? {
+                pages.extend(fs::read(page)?);
+            }
+            Ok(sha256::digest(pages))
+        }
+    }
+}
+
+/// Diffs every page `ref_path` and `cmp_path` (both rendered-page templates)
+/// produced, by page number, noting any pages only one side produced.
+#[allow(clippy::type_complexity)]
+fn rendered_diff(
+    ref_path: &Path,
+    cmp_path: &Path,
+) -> io::Result<(Vec<PageDiff>, Vec<u32>, Vec<u32>)> {
+    let ref_pages = rendered_pages_numbered(ref_path)?;
+    let cmp_pages = rendered_pages_numbered(cmp_path)?;
+    let out_dir = cmp_path.parent().unwrap_or_else(|| Path::new("."));
+    // Testing `*` against many compilers at once means many compilers' diff
+    // images can land in the same `out_dir`; qualify the file names with the
+    // compiler name (taken from `cmp_path`'s template, e.g.
+    // `v1-2-0-{p}.png`) so they don't overwrite each other.
+    let (_, file_prefix, _) = split_rendered_template(cmp_path);
+    let file_prefix = file_prefix.trim_end_matches('-');
+
+    let pages = diff::diff_pages(&ref_pages, &cmp_pages, out_dir, file_prefix)?;
+
+    let ref_page_numbers: HashSet<u32> =
+        ref_pages.iter().map(|(page, _)| *page).collect();
+    let cmp_page_numbers: HashSet<u32> =
+        cmp_pages.iter().map(|(page, _)| *page).collect();
+    let mut missing_pages: Vec<u32> =
+        ref_page_numbers.difference(&cmp_page_numbers).copied().collect();
+    missing_pages.sort_unstable();
+    let mut extra_pages: Vec<u32> =
+        cmp_page_numbers.difference(&ref_page_numbers).copied().collect();
+    extra_pages.sort_unstable();
+
+    Ok((pages, missing_pages, extra_pages))
+}
+
+/// Given an output path template containing the literal `{p}` page-number
+/// placeholder passed to the compiler for PNG rendering, returns the pages
+/// Typst actually produced, sorted by page number.
+fn rendered_pages(template: &Path) -> io::Result<Vec<PathBuf>> {
+    let pages = rendered_pages_numbered(template)?;
+    Ok(pages.into_iter().map(|(_, path)| path).collect())
+}
+
+/// Like [`rendered_pages`], but keeps each page's number alongside its path
+/// instead of discarding it, so callers that need to address a specific page
+/// number (e.g. [`Compiler::bless`] syncing the reference page set to the
+/// compare page set) don't have to re-derive it.
+fn rendered_pages_numbered(template: &Path) -> io::Result<Vec<(u32, PathBuf)>> {
+    let (dir, prefix, suffix) = split_rendered_template(template);
+
+    let mut pages: Vec<(u32, PathBuf)> = fs::read_dir(dir)?
+        .filter_map(Result::ok)
+        .filter_map(|entry| {
+            let name = entry.file_name().into_string().ok()?;
+            let page = name.strip_prefix(prefix)?.strip_suffix(suffix)?;
+            let page = page.parse().ok()?;
+            Some((page, entry.path()))
+        })
+        .collect();
+    pages.sort_by_key(|(page, _)| *page);
+    Ok(pages)
+}
+
+/// Builds the path a rendered output template would use for `page`, e.g.
+/// `"out-{p}.png"` with `page: 3` becomes `"out-3.png"`.
+fn rendered_page_path(template: &Path, page: u32) -> PathBuf {
+    let (dir, prefix, suffix) = split_rendered_template(template);
+    dir.join(format!("{prefix}{page}{suffix}"))
+}
+
+/// Splits a rendered output template into its directory and the prefix/
+/// suffix surrounding the literal `{p}` page-number placeholder.
+fn split_rendered_template(template: &Path) -> (&Path, &str, &str) {
+    let dir = template.parent().unwrap_or_else(|| Path::new("."));
+    let file_name = template
+        .file_name()
+        .and_then(|name| name.to_str())
+        .expect("a rendered output template must have a UTF-8 file name");
+    let (prefix, suffix) = file_name
+        .split_once("{p}")
+        .expect("a rendered output template must contain the {p} placeholder");
+    (dir, prefix, suffix)
+}
+
+/// Blanks out known nondeterministic PDF fields (the trailer `/ID` and the
+/// info dictionary's `/CreationDate`/`/ModDate`) so that otherwise
+/// byte-identical output compares equal regardless of when it was rendered.
+fn normalize_pdf(mut bytes: Vec<u8>) -> Vec<u8> {
+    for key in [&b"/CreationDate"[..], &b"/ModDate"[..]] {
+        blank_paren_value(&mut bytes, key);
+    }
+    blank_angle_value(&mut bytes, b"/ID");
+    bytes
+}
+
+/// Replaces the contents of the first `(...)` value following `key` with
+/// spaces, preserving the buffer's length.
+fn blank_paren_value(bytes: &mut [u8], key: &[u8]) {
+    let Some(key_pos) = find(bytes, key, 0) else { return };
+    let Some(open) = find(bytes, b"(", key_pos) else { return };
+    let Some(close) = find(bytes, b")", open) else { return };
+    for byte in &mut bytes[open + 1..close] {
+        *byte = b' ';
+    }
+}
+
+/// Replaces the hex digits inside the first two `<...>` values following
+/// `key` (the trailer `/ID`'s pair of hex strings) with zeros.
+fn blank_angle_value(bytes: &mut [u8], key: &[u8]) {
+    let Some(key_pos) = find(bytes, key, 0) else { return };
+    let mut pos = key_pos;
+    for _ in 0..2 {
+        let Some(open) = find(bytes, b"<", pos) else { return };
+        let Some(close) = find(bytes, b">", open) else { return };
+        for byte in &mut bytes[open + 1..close] {
+            if byte.is_ascii_hexdigit() {
+                *byte = b'0';
+            }
+        }
+        pos = close;
+    }
+}
+
+/// Returns the index of the first occurrence of `needle` in `bytes` at or
+/// after `from`.
+fn find(bytes: &[u8], needle: &[u8], from: usize) -> Option<usize> {
+    bytes[from..]
+        .windows(needle.len())
+        .position(|window| window == needle)
+        .map(|pos| from + pos)
+}
+
+/// Whether `name` is one of the dated pre-0.1.0 snapshot builds (e.g.
+/// `v2023-01-04`) rather than a semver-style release name (e.g. `v1-2-0`).
+/// These predate subcommands (and semver) entirely, so both
+/// [`CompilerArgLayout::from_compiler_name`] and [`version_key`] need to
+/// recognize every such name, not just the `v2023-0*` ones a narrower
+/// literal-prefix check happened to cover.
+fn is_date_named(name: &str) -> bool {
+    let Some(rest) = name.strip_prefix('v') else { return false };
+    let parts: Vec<&str> = rest.split('-').collect();
+    if parts.len() != 3 {
+        return false;
+    }
+
+    let is_digits =
+        |part: &str| !part.is_empty() && part.chars().all(|c| c.is_ascii_digit());
+    // A 4-digit year is what actually distinguishes a date (e.g.
+    // `2023-01-04`) from a short semver number (e.g. `1-2-0`); month and day
+    // just need to be plausible 1- or 2-digit numbers.
+    parts[0].len() == 4
+        && is_digits(parts[0])
+        && is_digits(parts[1])
+        && parts[1].len() <= 2
+        && is_digits(parts[2])
+        && parts[2].len() <= 2
+}
+
+#[derive(Clone, Debug)]
 enum CompilerArgLayout {
     // 0.7.0..
     SubcommandBeforeRoot,
@@ -398,7 +1348,7 @@ enum CompilerArgLayout {
 
 impl CompilerArgLayout {
     pub fn from_compiler_name(name: &str) -> Self {
-        if name.starts_with("v2023-0") {
+        if is_date_named(name) {
             return Self::NoSubcommand;
         }
         match name.split_once("v0-") {
@@ -445,8 +1395,16 @@ enum TestError {
     RefReadFailed(io::Error),
     #[error("failed to read the compile document")]
     CmpReadFailed(io::Error),
-    #[error("the documents don't match: {ref_digest} vs. {cmp_digest}")]
-    Mismatch { ref_digest: String, cmp_digest: String },
+    #[error("the documents don't match under {mode} comparison: {ref_digest} vs. {cmp_digest}")]
+    Mismatch { mode: CompareMode, ref_digest: String, cmp_digest: String },
+    #[error("the rendered pages don't match")]
+    RenderedMismatch {
+        pages: Vec<PageDiff>,
+        missing_pages: Vec<u32>,
+        extra_pages: Vec<u32>,
+    },
+    #[error("failed to compute the rendered page diff")]
+    DiffFailed(io::Error),
 }
 
 #[derive(Debug, Error)]
@@ -458,18 +1416,26 @@ enum CompileError {
 }
 
 impl CompileError {
-    pub fn log_unsuccessful_exit(&self) {
+    pub fn log_unsuccessful_exit(&self, log: &mut JobLog) {
         if let Self::UnsuccessfulExit { code, output } = self {
-            error!(
-                concat!(
-                    "The compiler exited with a code of {:?}.",
-                    " It wrote the following to stderr:",
-                ),
-                code,
-            );
+            log.error(format!(
+                "The compiler exited with a code of {code:?}. It wrote the \
+                 following to stderr:",
+            ));
             let output = String::from_utf8_lossy(&output.stderr);
             for line in output.lines() {
-                error!("> {line}");
+                log.error(format!("> {line}"));
+            }
+        }
+    }
+
+    /// The exit code and captured stderr, for including in a [`JobReport`].
+    /// Neither is available if the compiler never ran at all.
+    pub fn exit_info(&self) -> (Option<i32>, String) {
+        match self {
+            Self::IoError(_) => (None, String::new()),
+            Self::UnsuccessfulExit { code, output } => {
+                (*code, String::from_utf8_lossy(&output.stderr).into_owned())
             }
         }
     }
@@ -12,24 +12,31 @@
 // the License.
 
 use anyhow::{Context, Result};
-use backtrack_test_executor::args::Args;
+use backtrack_test_executor::args::{Args, Format};
 use clap::Parser;
 use log::{debug, LevelFilter};
 use simplelog::{ColorChoice, ConfigBuilder, TermLogger, TerminalMode};
 
 fn main() -> Result<()> {
     let args = Args::parse();
-    init_logging().context("failed to initialize logging")?;
+    init_logging(args.format).context("failed to initialize logging")?;
     debug!("Parsed args: {args:?}");
     backtrack_test_executor::run(args)
 }
 
-fn init_logging() -> Result<()> {
+fn init_logging(format: Format) -> Result<()> {
     let level = if cfg!(debug_assertions) {
         LevelFilter::Debug
     } else {
         LevelFilter::Info
     };
+    // `--format json` writes its one JSON document to stdout, so progress
+    // logging has to stay off of it entirely or it'll corrupt that output
+    // for a CI consumer; send everything to stderr instead in that case.
+    let terminal_mode = match format {
+        Format::Human => TerminalMode::Mixed,
+        Format::Json => TerminalMode::Stderr,
+    };
     TermLogger::init(
         level,
         ConfigBuilder::new()
@@ -37,7 +44,7 @@ fn init_logging() -> Result<()> {
             .set_thread_level(LevelFilter::Off)
             .set_target_level(LevelFilter::Off)
             .build(),
-        TerminalMode::Mixed,
+        terminal_mode,
         ColorChoice::Auto,
     )?;
     Ok(())
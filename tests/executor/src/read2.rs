@@ -0,0 +1,102 @@
+// Copyright © 2023 Luke Chambers
+// This file is part of Backtrack.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at <http://www.apache.org/licenses/LICENSE-2.0>.
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! A small port of `compiletest_rs`'s `read2` helper. Draining a child's
+//! stdout and stderr one after another can deadlock if the compiler fills up
+//! the pipe we're not currently reading from, so the two pipes are drained
+//! concurrently instead.
+
+use std::{
+    collections::VecDeque,
+    io::{self, Read},
+    process::{Child, Output},
+    thread,
+};
+
+/// The number of bytes kept from the start and the end of each stream; output
+/// in between is discarded as it's read (rather than read in full and
+/// truncated afterwards) so a runaway compiler can't exhaust memory.
+const ABBREVIATION_CAP: usize = 512 * 1024;
+
+/// The size of each chunk read from a pipe at a time.
+const CHUNK_SIZE: usize = 64 * 1024;
+
+/// Waits for `child` to exit, reading its stdout and stderr concurrently (one
+/// on a helper thread, the other on the caller's thread) so neither pipe can
+/// fill up and stall the compiler. Each stream is abbreviated to at most
+/// [`ABBREVIATION_CAP`] bytes.
+pub fn read2_abbreviated(mut child: Child) -> io::Result<Output> {
+    let mut stderr_pipe =
+        child.stderr.take().expect("the child has no stderr pipe");
+    let mut stdout_pipe =
+        child.stdout.take().expect("the child has no stdout pipe");
+
+    let stderr_thread =
+        thread::spawn(move || read_abbreviated(&mut stderr_pipe));
+
+    let stdout = read_abbreviated(&mut stdout_pipe)?;
+    let stderr = match stderr_thread.join() {
+        Ok(result) => result?,
+        Err(_) => {
+            return Err(io::Error::other("the stderr reader thread panicked"))
+        }
+    };
+    let status = child.wait()?;
+
+    Ok(Output { status, stdout, stderr })
+}
+
+/// Reads all of `reader`, keeping at most [`ABBREVIATION_CAP`] bytes in
+/// memory at once: the first half is kept from the start of the stream, and
+/// the second half is a sliding window over its end. Bytes outside those two
+/// halves are dropped as soon as they're read instead of being buffered in
+/// full and truncated afterwards, so the amount of memory this holds doesn't
+/// grow with the size of the stream.
+fn read_abbreviated<R: Read>(reader: &mut R) -> io::Result<Vec<u8>> {
+    let half = ABBREVIATION_CAP / 2;
+
+    let mut head = Vec::new();
+    let mut tail = VecDeque::with_capacity(half);
+    let mut total = 0u64;
+    let mut chunk = [0u8; CHUNK_SIZE];
+    loop {
+        let read = reader.read(&mut chunk)?;
+        if read == 0 {
+            break;
+        }
+        total += read as u64;
+
+        let data = &chunk[..read];
+        if head.len() < half {
+            let take = (half - head.len()).min(data.len());
+            head.extend_from_slice(&data[..take]);
+            tail.extend(data[take..].iter().copied());
+        } else {
+            tail.extend(data.iter().copied());
+        }
+        while tail.len() > half {
+            tail.pop_front();
+        }
+    }
+
+    let mut buf = head;
+    if total <= ABBREVIATION_CAP as u64 {
+        buf.extend(tail);
+        return Ok(buf);
+    }
+
+    let dropped = total - buf.len() as u64 - tail.len() as u64;
+    buf.extend(format!("\n... {dropped} bytes omitted ...\n").into_bytes());
+    buf.extend(tail);
+    Ok(buf)
+}
@@ -0,0 +1,97 @@
+// Copyright © 2023 Luke Chambers
+// This file is part of Backtrack.
+//
+// Licensed under the Apache License, Version 2.0 (the "License"); you may not
+// use this file except in compliance with the License. You may obtain a copy of
+// the License at <http://www.apache.org/licenses/LICENSE-2.0>.
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS, WITHOUT
+// WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied. See the
+// License for the specific language governing permissions and limitations under
+// the License.
+
+//! A serializable record of a single job's outcome. `JobReport` backs both
+//! the human-readable summary table and `--format json`, so the two never
+//! drift apart.
+
+use serde::Serialize;
+
+use crate::diff::PageDiff;
+
+/// The result of running one compiler against one sample.
+#[derive(Serialize)]
+pub struct JobReport {
+    pub sample: String,
+    pub compiler: String,
+    pub op: &'static str,
+    pub reported_version: Option<String>,
+    pub outcome: Outcome,
+    pub ref_digest: Option<String>,
+    pub cmp_digest: Option<String>,
+    pub exit_code: Option<i32>,
+    pub stderr: String,
+}
+
+impl JobReport {
+    /// Whether this job should count against the run's overall success.
+    pub fn is_failure(&self) -> bool {
+        match &self.outcome {
+            Outcome::Err { .. } => true,
+            Outcome::Mismatch { expected, .. } => !expected,
+            Outcome::Ok | Outcome::Blessed | Outcome::ExpectedMismatch { .. } => {
+                false
+            }
+        }
+    }
+
+    /// Whether this job produced a mismatch the sample didn't already expect,
+    /// i.e. one worth pointing the user at the compare directory for.
+    pub fn is_unexpected_mismatch(&self) -> bool {
+        matches!(self.outcome, Outcome::Mismatch { expected: false, .. })
+    }
+}
+
+/// The outcome of a single job, as reported in [`JobReport::outcome`].
+#[derive(Serialize)]
+#[serde(tag = "kind", rename_all = "snake_case")]
+pub enum Outcome {
+    Ok,
+    /// `expected` is set when the sample's `expect: mismatch` directive
+    /// already accounted for this mismatch. `mode` is the comparison mode
+    /// (`digest`, `normalized`, or `rendered`) that detected it.
+    Mismatch {
+        expected: bool,
+        mode: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rendered_diff: Option<RenderedDiff>,
+    },
+    /// Like `Mismatch { expected: true, .. }`, but the mismatch was
+    /// documented in the `--known-mismatches` allowlist rather than the
+    /// sample's own `expect` directive.
+    ExpectedMismatch {
+        mode: &'static str,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        rendered_diff: Option<RenderedDiff>,
+    },
+    Blessed,
+    Err { stage: String },
+}
+
+/// The per-page visual diff data for a `--compare-mode rendered` mismatch,
+/// so `--format json` consumers get the same page-level detail the human
+/// table logs instead of just the bare digests (which rendered mode doesn't
+/// even have).
+#[derive(Serialize)]
+pub struct RenderedDiff {
+    pub pages: Vec<PageDiff>,
+    pub missing_pages: Vec<u32>,
+    pub extra_pages: Vec<u32>,
+}
+
+/// The full set of job results for a run, suitable for `--format json`.
+#[derive(Serialize)]
+pub struct Report {
+    pub success: bool,
+    pub jobs: Vec<JobReport>,
+}